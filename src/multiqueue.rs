@@ -1,16 +1,20 @@
 
 use std::cell::Cell;
+use std::fmt;
 use std::mem;
 use std::ptr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicPtr, AtomicUsize, fence};
 use std::sync::atomic::Ordering::{Relaxed, Acquire, Release, AcqRel};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use alloc;
 use atomicsignal::LoadedSignal;
 use countedindex::{CountedIndex, get_valid_wrap, Index, INITIAL_QUEUE_FLAG};
 use maybe_acquire::{maybe_acquire_fence, MAYBE_ACQUIRE};
 use memory::{MemoryManager, MemToken};
+use waker::{TaskWaker, Waker};
 
 use read_cursor::{ReadCursor, Reader};
 
@@ -35,6 +39,12 @@ struct MultiQueue<T> {
     head: CountedIndex,
     tail_cache: AtomicUsize,
     writers: AtomicUsize,
+    // The number of live broadcast reader groups, mirroring `writers`
+    // on the reader side: incremented by `add_reader` (which creates a
+    // new group), decremented when the last consumer of a group drops.
+    // `MultiWriter::send` reads this to tell "queue momentarily full"
+    // apart from "nobody can ever drain it again".
+    reader_groups: AtomicUsize,
     d2: [u8; 64],
 
     // Shared Data
@@ -45,6 +55,18 @@ struct MultiQueue<T> {
     tail: ReadCursor,
     data: *mut QueueEntry<T>,
     capacity: isize,
+
+    // Wait lists for blocking recv/send, plus their async counterparts
+    // used by the Stream/Sink adapters. Both sides are touched from
+    // either thread: readers register on reader_waker/reader_task_waker
+    // and are woken by a producer's commit, writers register on
+    // writer_waker/writer_task_waker and are woken by a reader's tail
+    // advance, so they live in the shared section rather than the
+    // writer-only one above.
+    reader_waker: Waker,
+    writer_waker: Waker,
+    reader_task_waker: TaskWaker,
+    writer_task_waker: TaskWaker,
     d3: [u8; 64],
 
     manager: MemoryManager,
@@ -86,12 +108,17 @@ impl<T> MultiQueue<T> {
             head: CountedIndex::new(capacity),
             tail_cache: AtomicUsize::new(0),
             writers: AtomicUsize::new(1),
+            reader_groups: AtomicUsize::new(1),
             d2: unsafe { mem::uninitialized() },
 
             tail: cursor,
             data: queuedat,
             capacity: capacity as isize,
 
+            reader_waker: Waker::new(),
+            writer_waker: Waker::new(),
+            reader_task_waker: TaskWaker::new(),
+            writer_task_waker: TaskWaker::new(),
             d3: unsafe { mem::uninitialized() },
 
             manager: MemoryManager::new(),
@@ -145,6 +172,8 @@ impl<T> MultiQueue<T> {
                     None => {
                         ptr::write(&mut write_cell.val, val);
                         write_cell.wraps.store(wrap_valid_tag, Release);
+                        self.reader_waker.wake_all();
+                        self.reader_task_waker.wake_all();
                         return Ok(());
                     }
                 }
@@ -168,6 +197,8 @@ impl<T> MultiQueue<T> {
             ptr::write(&mut write_cell.val, val);
             transaction.commit_direct(1, Relaxed);
             write_cell.wraps.store(wrap_valid_tag, Release);
+            self.reader_waker.wake_all();
+            self.reader_task_waker.wake_all();
             Ok(())
         }
     }
@@ -188,12 +219,28 @@ impl<T> MultiQueue<T> {
                         ctail_attempt = new_attempt;
                         mem::forget(rval);
                     }
-                    None => return Some(rval),
+                    None => {
+                        self.writer_waker.wake_all();
+                        self.writer_task_waker.wake_all();
+                        return Some(rval);
+                    }
                 }
             }
         }
     }
 
+    /// Checks whether `reader` has an item available without popping it,
+    /// used by `Select` to probe several queues without committing to
+    /// any one of them.
+    pub fn is_ready(&self, reader: &Reader) -> bool {
+        let ctail_attempt = reader.load_attempt(Relaxed);
+        let (ctail, wrap_valid_tag) = ctail_attempt.get();
+        unsafe {
+            let read_cell = &*self.data.offset(ctail);
+            read_cell.wraps.load(MAYBE_ACQUIRE) == wrap_valid_tag
+        }
+    }
+
     pub fn pop_view<R, F: FnOnce(&T) -> R>(&self, op: F, reader: &Reader) -> Result<R, F> {
         let mut ctail_attempt = reader.load_attempt(Relaxed);
         unsafe {
@@ -205,6 +252,8 @@ impl<T> MultiQueue<T> {
             maybe_acquire_fence();
             let rval = op(&read_cell.val);
             ctail_attempt.commit_direct(1, Release);
+            self.writer_waker.wake_all();
+            self.writer_task_waker.wake_all();
             Ok(rval)
         }
     }
@@ -260,6 +309,55 @@ impl<T> MultiWriter<T> {
     /// Removes the writer as a producer to the queue
     pub fn unsubscribe(self) {}
 
+    /// Registers a task waker to be woken the next time a reader frees a
+    /// slot. Used by the `Sink` adapter.
+    #[inline(always)]
+    pub(crate) fn register_task_waker(&self, waker: &::std::task::Waker) {
+        self.queue.writer_task_waker.register(waker);
+    }
+
+    /// Checks whether any reader remains in any broadcast group. Used
+    /// by the `Sink` adapter to fail instead of waiting on a slot that
+    /// will never be read.
+    #[inline(always)]
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.queue.reader_groups.load(Acquire) == 0
+    }
+
+    /// Pushes `val` onto the queue, parking the current thread while the
+    /// queue is full instead of spinning. Fails if no reader remains in
+    /// any broadcast group, since the value could then never be
+    /// consumed.
+    pub fn send(&self, val: T) -> Result<(), SendError<T>> {
+        let mut val = val;
+        loop {
+            if self.queue.reader_groups.load(Acquire) == 0 {
+                return Err(SendError(val));
+            }
+            match self.push(val) {
+                Ok(()) => return Ok(()),
+                Err(v) => val = v,
+            }
+            self.queue.writer_waker.register();
+            if self.queue.reader_groups.load(Acquire) == 0 {
+                self.queue.writer_waker.unregister(&thread::current());
+                return Err(SendError(val));
+            }
+            match self.push(val) {
+                Ok(()) => {
+                    self.queue.writer_waker.unregister(&thread::current());
+                    return Ok(());
+                }
+                Err(v) => val = v,
+            }
+            thread::park();
+            // Either a pop woke us or this was spurious; either way
+            // this round's registration is done, so drop it rather than
+            // leaving it to be drained by some unrelated future pop.
+            self.queue.writer_waker.unregister(&thread::current());
+        }
+    }
+
     #[cold]
     #[inline(never)]
     fn handle_signals(&self, signal: LoadedSignal) {
@@ -271,6 +369,52 @@ impl<T> MultiWriter<T> {
     }
 }
 
+/// The error returned by `recv` when no writer remains that could ever
+/// produce another item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "receiving on a disconnected queue")
+    }
+}
+
+/// The error returned by `recv_timeout`/`recv_deadline` when no item
+/// became available before the deadline, or no writer remains that
+/// could ever produce one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
+/// The error returned by `try_recv` when no item is currently
+/// available, distinguishing an empty queue from one that can never
+/// yield another item because every writer has dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+/// The error returned by `send` when no reader remains in any broadcast
+/// group, meaning the value could never be consumed. Carries the value
+/// back so the caller can recover it, mirroring `std::sync::mpsc`.
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SendError(..)")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "sending on a queue with no readers left")
+    }
+}
+
 impl<T> MultiReader<T> {
     #[inline(always)]
     pub fn pop(&self) -> Option<T> {
@@ -278,7 +422,138 @@ impl<T> MultiReader<T> {
         self.queue.pop(&self.reader)
     }
 
+    /// Pops a value from the queue without blocking, distinguishing an
+    /// empty-but-live queue from one no writer can ever push to again.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.examine_signals();
+        match self.queue.pop(&self.reader) {
+            Some(val) => Ok(val),
+            None => {
+                if self.queue.writers.load(Acquire) == 0 {
+                    // A final push may have landed before the last
+                    // writer dropped; give pop one more chance.
+                    match self.queue.pop(&self.reader) {
+                        Some(val) => Ok(val),
+                        None => Err(TryRecvError::Disconnected),
+                    }
+                } else {
+                    Err(TryRecvError::Empty)
+                }
+            }
+        }
+    }
+
+    /// Pops a value from the queue, parking the current thread while the
+    /// queue is empty instead of spinning. Returns `Err(RecvError)` once
+    /// every writer has dropped and the queue has drained, rather than
+    /// parking forever waiting for a push that can never come.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.examine_signals();
+        loop {
+            if let Some(val) = self.queue.pop(&self.reader) {
+                return Ok(val);
+            }
+            if self.queue.writers.load(Acquire) == 0 {
+                // A final push may have landed before the last writer
+                // dropped; give pop one more chance before giving up.
+                return match self.queue.pop(&self.reader) {
+                    Some(val) => Ok(val),
+                    None => Err(RecvError),
+                };
+            }
+            self.queue.reader_waker.register();
+            if let Some(val) = self.queue.pop(&self.reader) {
+                self.queue.reader_waker.unregister(&thread::current());
+                return Ok(val);
+            }
+            thread::park();
+            // Either a push woke us or this was spurious; either way
+            // this round's registration is done, so drop it rather than
+            // leaving it to be drained by some unrelated future push.
+            self.queue.reader_waker.unregister(&thread::current());
+        }
+    }
+
+    /// Like `recv`, but gives up after `timeout` has elapsed.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Instant::now() + timeout)
+    }
+
+    /// Like `recv`, but gives up once `deadline` has passed. The
+    /// deadline is rechecked after every spurious wakeup so the total
+    /// wait never exceeds what was requested.
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        self.examine_signals();
+        loop {
+            if let Some(val) = self.queue.pop(&self.reader) {
+                return Ok(val);
+            }
+            if self.queue.writers.load(Acquire) == 0 {
+                // A final push may have landed before the last writer
+                // dropped; give pop one more chance before giving up.
+                return match self.queue.pop(&self.reader) {
+                    Some(val) => Ok(val),
+                    None => Err(RecvTimeoutError::Disconnected),
+                };
+            }
+            self.queue.reader_waker.register();
+            if let Some(val) = self.queue.pop(&self.reader) {
+                self.queue.reader_waker.unregister(&thread::current());
+                return Ok(val);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                self.queue.reader_waker.unregister(&thread::current());
+                return Err(RecvTimeoutError::Timeout);
+            }
+            thread::park_timeout(deadline - now);
+            // Either a push woke us or this was a spurious/timeout
+            // wakeup; either way this round's registration is done, so
+            // drop it rather than leaking one `Thread` per call into the
+            // wait list for the lifetime of the queue.
+            self.queue.reader_waker.unregister(&thread::current());
+        }
+    }
+
+    /// Checks whether this reader has an item available without popping
+    /// it. Used by `Select` to probe several queues at once.
+    #[inline(always)]
+    pub fn is_ready(&self) -> bool {
+        self.queue.is_ready(&self.reader)
+    }
+
+    /// Registers the current thread to be woken the next time a writer
+    /// commits a push. Used by `Select` alongside `is_ready` to park
+    /// without missing a wakeup.
+    #[inline(always)]
+    pub(crate) fn register_thread_waker(&self) {
+        self.queue.reader_waker.register();
+    }
+
+    /// Drops the current thread's registration from this reader's wait
+    /// list, if present. Used by `Select` once it wakes up, so its
+    /// registration isn't left behind on the queues that didn't fire.
+    #[inline(always)]
+    pub(crate) fn unregister_thread_waker(&self) {
+        self.queue.reader_waker.unregister(&thread::current());
+    }
+
+    /// Registers a task waker to be woken the next time a writer commits
+    /// a push. Used by the `Stream` adapter.
+    #[inline(always)]
+    pub(crate) fn register_task_waker(&self, waker: &::std::task::Waker) {
+        self.queue.reader_task_waker.register(waker);
+    }
+
+    /// Checks whether any writer remains. Used by `Select` to stop
+    /// waiting on a set of readers that can never produce another item.
+    #[inline(always)]
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.queue.writers.load(Acquire) == 0
+    }
+
     pub fn add_reader(&self) -> MultiReader<T> {
+        self.queue.reader_groups.fetch_add(1, Release);
         MultiReader {
             queue: self.queue.clone(),
             reader: self.queue.tail.add_reader(&self.reader, &self.queue.manager),
@@ -343,6 +618,33 @@ impl<T> SingleReader<T> {
         self.reader.pop()
     }
 
+    /// Pops a value from the queue without blocking, distinguishing an
+    /// empty-but-live queue from one no writer can ever push to again.
+    #[inline(always)]
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.reader.try_recv()
+    }
+
+    /// Pops a value from the queue, parking the current thread while the
+    /// queue is empty instead of spinning. Returns `Err(RecvError)` once
+    /// every writer has dropped and the queue has drained.
+    #[inline(always)]
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.reader.recv()
+    }
+
+    /// Like `recv`, but gives up after `timeout` has elapsed.
+    #[inline(always)]
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.reader.recv_timeout(timeout)
+    }
+
+    /// Like `recv`, but gives up once `deadline` has passed.
+    #[inline(always)]
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        self.reader.recv_deadline(deadline)
+    }
+
     #[inline(always)]
     pub fn pop_view<R, F: FnOnce(&T) -> R>(&self, op: F) -> Result<R, F> {
         self.reader.examine_signals();
@@ -384,7 +686,14 @@ impl<T> Clone for MultiReader<T> {
 
 impl<T> Drop for MultiWriter<T> {
     fn drop(&mut self) {
-        self.queue.writers.fetch_sub(1, Release);
+        if self.queue.writers.fetch_sub(1, Release) == 1 {
+            // This was the last writer: wake every parked/polling reader
+            // so a blocking `recv`/`recv_deadline` or a `Stream` notices
+            // the disconnect instead of waiting for a push that can
+            // never come.
+            self.queue.reader_waker.wake_all();
+            self.queue.reader_task_waker.wake_all();
+        }
         self.queue.manager.remove_token(self.token);
     }
 }
@@ -394,6 +703,14 @@ impl<T> Drop for MultiReader<T> {
         if self.reader.remove_consumer() == 1 {
             self.queue.tail.remove_reader(&self.reader, &self.queue.manager);
             self.queue.manager.remove_token(self.token);
+            if self.queue.reader_groups.fetch_sub(1, Release) == 1 {
+                // This was the last broadcast reader group: wake every
+                // parked/polling writer so a blocking `send` or a
+                // `Sink` notices the disconnect instead of waiting for
+                // a slot that will never be read.
+                self.queue.writer_waker.wake_all();
+                self.queue.writer_task_waker.wake_all();
+            }
         }
     }
 }
@@ -419,7 +736,9 @@ mod test {
 
     use std::sync::atomic::Ordering::*;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
     use std::thread::yield_now;
+    use std::time::{Duration, Instant};
 
     use std::sync::Barrier;
 
@@ -541,8 +860,6 @@ mod test {
         let bref = &myb;
         let num_loop = 100000;
         let counter = AtomicUsize::new(0);
-        let writers_active = AtomicUsize::new(senders);
-        let waref = &writers_active;
         let cref = &counter;
         scope(|scope| {
             for q in 0..senders {
@@ -556,10 +873,8 @@ mod test {
                             }
                             yield_now();
                         }
-                        waref.fetch_sub(1, Relaxed);
                         assert!(false, "Writer could not write");
                     }
-                    waref.fetch_sub(1, Release);
                 });
             }
             writer.unsubscribe();
@@ -574,13 +889,12 @@ mod test {
                         }
                         bref.wait();
                         loop {
-                            if let Some(val) = this_reader.pop() {
-                                cref.fetch_add(1, Ordering::Relaxed);
-                            } else {
-                                let writers = waref.load(Ordering::Acquire);
-                                if writers == 0 {
-                                    break;
+                            match this_reader.try_recv() {
+                                Ok(_) => {
+                                    cref.fetch_add(1, Ordering::Relaxed);
                                 }
+                                Err(TryRecvError::Disconnected) => break,
+                                Err(TryRecvError::Empty) => {}
                             }
                             yield_now();
                         }
@@ -613,4 +927,188 @@ mod test {
         mpmc_broadcast(2, 2, 2);
     }
 
+    #[test]
+    fn test_recv_blocks_until_push() {
+        let (writer, reader) = MultiQueue::<usize>::new(1);
+        scope(|scope| {
+            scope.spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                writer.push(42).expect("queue starts empty");
+            });
+            assert_eq!(42, reader.recv().expect("writer is still alive"));
+        });
+    }
+
+    #[test]
+    fn test_recv_disconnects_when_writers_drop() {
+        let (writer, reader) = MultiQueue::<usize>::new(1);
+        drop(writer);
+        assert_eq!(Err(RecvError), reader.recv());
+    }
+
+    #[test]
+    fn test_send_blocks_until_pop() {
+        let (writer, reader) = MultiQueue::<usize>::new(1);
+        writer.push(1).expect("queue starts empty");
+        scope(|scope| {
+            let popper = scope.spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                assert_eq!(1, reader.recv().expect("writer is still alive"));
+                // Hand the reader back so it outlives `send` below:
+                // dropping it here would disconnect the queue, and `send`
+                // checks for that before retrying its push.
+                reader
+            });
+            writer.send(2).expect("reader is still alive");
+            drop(popper.join());
+        });
+    }
+
+    #[test]
+    fn test_send_disconnects_when_readers_drop() {
+        let (writer, reader) = MultiQueue::<usize>::new(1);
+        assert!(reader.unsubscribe());
+        assert!(writer.send(1).is_err());
+    }
+
+    #[test]
+    fn test_stream_yields_pushed_values() {
+        extern crate futures;
+        use self::futures::executor::block_on;
+        use self::futures::stream::StreamExt;
+
+        let (writer, reader) = MultiQueue::<usize>::new(4);
+        writer.push(1).unwrap();
+        writer.push(2).unwrap();
+        drop(writer);
+
+        let mut stream = reader.into_stream();
+        assert_eq!(Some(1), block_on(stream.next()));
+        assert_eq!(Some(2), block_on(stream.next()));
+        // Every writer has dropped and the queue is drained: the stream
+        // ends instead of pending forever.
+        assert_eq!(None, block_on(stream.next()));
+    }
+
+    #[test]
+    fn test_sink_send_and_disconnect() {
+        extern crate futures;
+        use self::futures::executor::block_on;
+        use self::futures::sink::SinkExt;
+
+        let (writer, reader) = MultiQueue::<usize>::new(4);
+        let mut sink = writer.into_sink();
+        block_on(sink.send(1)).expect("reader is still alive");
+        assert_eq!(1, reader.recv().unwrap());
+
+        reader.unsubscribe();
+        assert!(block_on(sink.send(2)).is_err());
+    }
+
+    #[test]
+    fn test_select_picks_the_ready_queue() {
+        use select::Select;
+
+        let (writer_a, reader_a) = MultiQueue::<usize>::new(4);
+        let (writer_b, reader_b) = MultiQueue::<usize>::new(4);
+
+        let mut select = Select::new();
+        let idx_a = select.add(&reader_a);
+        let idx_b = select.add(&reader_b);
+
+        writer_b.push(9).unwrap();
+        let (ready_idx, val) = select.recv().expect("writer_b is still alive");
+        assert_eq!(idx_b, ready_idx);
+        assert_eq!(9, val);
+
+        writer_a.push(3).unwrap();
+        let (ready_idx, val) = select.recv().expect("writer_a is still alive");
+        assert_eq!(idx_a, ready_idx);
+        assert_eq!(3, val);
+    }
+
+    #[test]
+    fn test_select_blocks_until_a_push_wakes_it() {
+        use select::Select;
+
+        let (writer, reader) = MultiQueue::<usize>::new(4);
+        let mut select = Select::new();
+        select.add(&reader);
+
+        scope(|scope| {
+            scope.spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                writer.push(1).unwrap();
+            });
+            let (idx, val) = select.recv().expect("writer is still alive");
+            assert_eq!(0, idx);
+            assert_eq!(1, val);
+        });
+    }
+
+    #[test]
+    fn test_select_reports_disconnect() {
+        use select::Select;
+
+        let (writer, reader) = MultiQueue::<usize>::new(4);
+        drop(writer);
+
+        let mut select = Select::new();
+        select.add(&reader);
+        assert_eq!(None, select.ready());
+        assert_eq!(None, select.recv());
+    }
+
+    #[test]
+    fn test_recv_timeout_bounds_the_wait() {
+        let (_writer, reader) = MultiQueue::<usize>::new(1);
+        let timeout = Duration::from_millis(50);
+        let start = Instant::now();
+        assert_eq!(Err(RecvTimeoutError::Timeout), reader.recv_timeout(timeout));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= timeout);
+        // Generous upper bound so this isn't flaky under load, but still
+        // tight enough to catch a timeout that isn't actually enforced.
+        assert!(elapsed < timeout * 10);
+    }
+
+    #[test]
+    fn test_recv_deadline_returns_a_pushed_value() {
+        let (writer, reader) = MultiQueue::<usize>::new(1);
+        writer.push(7).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        assert_eq!(Ok(7), reader.recv_deadline(deadline));
+    }
+
+    #[test]
+    fn test_recv_deadline_reports_disconnect() {
+        let (writer, reader) = MultiQueue::<usize>::new(1);
+        drop(writer);
+        let deadline = Instant::now() + Duration::from_secs(1);
+        assert_eq!(Err(RecvTimeoutError::Disconnected), reader.recv_deadline(deadline));
+    }
+
+    #[test]
+    fn test_try_recv_distinguishes_empty_from_disconnected() {
+        let (writer, reader) = MultiQueue::<usize>::new(1);
+        assert_eq!(Err(TryRecvError::Empty), reader.try_recv());
+        writer.push(5).unwrap();
+        assert_eq!(Ok(5), reader.try_recv());
+        assert_eq!(Err(TryRecvError::Empty), reader.try_recv());
+        drop(writer);
+        assert_eq!(Err(TryRecvError::Disconnected), reader.try_recv());
+    }
+
+    #[test]
+    fn test_send_fails_once_every_reader_group_is_gone() {
+        let (writer, reader) = MultiQueue::<usize>::new(1);
+        let reader_2 = reader.add_reader();
+        assert!(reader.unsubscribe());
+        assert!(reader_2.unsubscribe());
+        match writer.send(1) {
+            Err(SendError(val)) => assert_eq!(1, val),
+            Ok(()) => panic!("send should fail with no readers left"),
+        }
+    }
+
 }
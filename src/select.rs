@@ -0,0 +1,103 @@
+//! `select`-style multiplexing across several `MultiReader`s, analogous
+//! to crossbeam-channel's `select` subsystem.
+
+use std::thread;
+
+use multiqueue::MultiReader;
+
+/// Waits on readiness of several `MultiReader`s at once.
+///
+/// `ready()` never pops a value itself: it only reports which reader
+/// has one available, leaving the caller free to `pop()`,
+/// `pop_view()`, or hand the choice off elsewhere. `recv()` is the
+/// convenience that also pops from the reader `ready()` picked.
+pub struct Select<'a, T: 'a> {
+    readers: Vec<&'a MultiReader<T>>,
+}
+
+impl<'a, T> Select<'a, T> {
+    pub fn new() -> Select<'a, T> {
+        Select { readers: Vec::new() }
+    }
+
+    /// Adds a reader to the set being selected over, returning the
+    /// index `ready()` will report it by.
+    pub fn add(&mut self, reader: &'a MultiReader<T>) -> usize {
+        self.readers.push(reader);
+        self.readers.len() - 1
+    }
+
+    fn probe(&self) -> Option<usize> {
+        self.readers.iter().position(|r| r.is_ready())
+    }
+
+    /// True once every participating reader has lost all its writers,
+    /// meaning none of them can ever become ready again.
+    fn all_disconnected(&self) -> bool {
+        self.readers.iter().all(|r| r.is_disconnected())
+    }
+
+    /// Blocks until one of the added readers has an item available,
+    /// returning its index, or `None` once every reader has disconnected
+    /// and none had an item available. Does not pop the item, so the
+    /// caller must still call `pop()` on the reader at that index (and
+    /// be prepared for it to occasionally come back empty, since another
+    /// thread may have raced it to the item).
+    ///
+    /// `Select` is driven from a single thread at a time (it isn't
+    /// `Sync`), so unlike crossbeam-channel's selector there is never
+    /// more than one in-flight registration round to arbitrate between;
+    /// no separate "claimed" sentinel is needed to avoid a wakeup being
+    /// double-counted across queues. What does need care is that a
+    /// round's registration is *removed* from every queue that didn't
+    /// fire once we wake up, or those wait lists would grow without
+    /// bound and a stale entry could eat a later, unrelated `park()`'s
+    /// wakeup token on this thread.
+    pub fn ready(&self) -> Option<usize> {
+        loop {
+            if let Some(i) = self.probe() {
+                return Some(i);
+            }
+            if self.all_disconnected() {
+                return None;
+            }
+            // Register on every queue before re-probing: wake_all only
+            // drains wakers that were registered by the time it ran, so
+            // a push that lands between the probe above and this
+            // registration won't unpark us; the re-probe below catches
+            // it instead, and any later push does unpark us.
+            for reader in &self.readers {
+                reader.register_thread_waker();
+            }
+            let found = self.probe();
+            let disconnected = found.is_none() && self.all_disconnected();
+            if found.is_none() && !disconnected {
+                thread::park();
+            }
+            // Whether we found an item, gave up, or just woke up, this
+            // round's registration is done: drop it from every queue so
+            // none of them carry it into the next round.
+            for reader in &self.readers {
+                reader.unregister_thread_waker();
+            }
+            if let Some(i) = found {
+                return Some(i);
+            }
+            if disconnected {
+                return None;
+            }
+        }
+    }
+
+    /// Blocks until one of the added readers yields an item, returning
+    /// its index alongside the value, or `None` once every reader has
+    /// disconnected and drained.
+    pub fn recv(&self) -> Option<(usize, T)> {
+        loop {
+            let i = self.ready()?;
+            if let Some(val) = self.readers[i].pop() {
+                return Some((i, val));
+            }
+        }
+    }
+}
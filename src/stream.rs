@@ -0,0 +1,114 @@
+//! `futures::Stream`/`futures::Sink` adapters over `MultiReader`/
+//! `MultiWriter`, so the queue can be driven from an async executor
+//! (tokio, smol, ...) instead of dedicating an OS thread to spinning.
+//! These reuse the same register-recheck wakeup machinery as the
+//! blocking `recv`/`send` methods, but register a `std::task::Waker`
+//! clone instead of parking the calling thread.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::sink::Sink;
+use futures::stream::Stream;
+
+use multiqueue::{MultiReader, MultiWriter, SendError, TryRecvError};
+
+/// A `Stream` over the values pushed to the corresponding `MultiWriter`s,
+/// returned by `MultiReader::into_stream`.
+pub struct MultiReaderStream<T> {
+    reader: MultiReader<T>,
+}
+
+impl<T> MultiReader<T> {
+    /// Converts this reader into a `Stream` that can be polled from an
+    /// async executor instead of spun on in a loop.
+    pub fn into_stream(self) -> MultiReaderStream<T> {
+        MultiReaderStream { reader: self }
+    }
+}
+
+impl<T> Stream for MultiReaderStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+        let reader = &self.reader;
+        match reader.try_recv() {
+            Ok(val) => return Poll::Ready(Some(val)),
+            Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+            Err(TryRecvError::Empty) => {}
+        }
+        reader.register_task_waker(cx.waker());
+        match reader.try_recv() {
+            Ok(val) => Poll::Ready(Some(val)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+}
+
+/// A `Sink` accepting values for the corresponding `MultiReader`s,
+/// returned by `MultiWriter::into_sink`.
+pub struct MultiWriterSink<T> {
+    writer: MultiWriter<T>,
+    buffered: Option<T>,
+}
+
+impl<T> MultiWriter<T> {
+    /// Converts this writer into a `Sink` that can be driven from an
+    /// async executor instead of spun on in a loop.
+    pub fn into_sink(self) -> MultiWriterSink<T> {
+        MultiWriterSink { writer: self, buffered: None }
+    }
+}
+
+impl<T> MultiWriterSink<T> {
+    /// Pushes the buffered value (if any), registering for a wakeup and
+    /// failing with the value still attached if no reader remains to
+    /// ever drain it, mirroring `MultiWriter::send`'s `SendError` rather
+    /// than silently discarding a value the caller might still want.
+    fn try_flush_buffered(&mut self, cx: &mut Context) -> Poll<Result<(), SendError<T>>> {
+        while let Some(val) = self.buffered.take() {
+            if self.writer.is_disconnected() {
+                return Poll::Ready(Err(SendError(val)));
+            }
+            match self.writer.push(val) {
+                Ok(()) => {}
+                Err(val) => {
+                    self.buffered = Some(val);
+                    self.writer.register_task_waker(cx.waker());
+                    if self.writer.is_disconnected() {
+                        return Poll::Ready(Err(SendError(self.buffered.take().unwrap())));
+                    }
+                    if let Err(val) = self.writer.push(self.buffered.take().unwrap()) {
+                        self.buffered = Some(val);
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> Sink<T> for MultiWriterSink<T> {
+    type Error = SendError<T>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), SendError<T>>> {
+        self.get_mut().try_flush_buffered(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), SendError<T>> {
+        let this = self.get_mut();
+        debug_assert!(this.buffered.is_none());
+        this.buffered = Some(item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), SendError<T>>> {
+        self.get_mut().try_flush_buffered(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), SendError<T>>> {
+        self.poll_flush(cx)
+    }
+}
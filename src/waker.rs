@@ -0,0 +1,95 @@
+//! A wait list of parked OS threads, used to avoid spinning when a queue
+//! is empty (readers) or full (writers). Modeled loosely on
+//! crossbeam-channel's `waker.rs`: a waiting thread registers itself,
+//! then re-checks the condition it is waiting on before actually
+//! parking, so a wakeup that races the registration is never lost.
+//!
+//! `wake_all` is called from every successful push/pop, so it has to be
+//! cheap when nobody is actually waiting (the overwhelmingly common
+//! case on the hot path): a `has_sleepers` flag lets it skip the
+//! `Mutex` entirely instead of locking on every single operation. The
+//! flag can go stale relative to a concurrent `register`, but that's
+//! harmless: whoever just registered always re-checks the condition
+//! they're waiting on before parking, so a wakeup this call misses is
+//! caught by that re-check instead.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::task::Waker as TaskWakerHandle;
+use std::thread::{self, Thread};
+
+pub struct Waker {
+    has_sleepers: AtomicBool,
+    sleepers: Mutex<Vec<Thread>>,
+}
+
+impl Waker {
+    pub fn new() -> Waker {
+        Waker {
+            has_sleepers: AtomicBool::new(false),
+            sleepers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers the current thread to be woken by the next `wake_all`.
+    pub fn register(&self) {
+        self.sleepers.lock().unwrap().push(thread::current());
+        self.has_sleepers.store(true, Ordering::SeqCst);
+    }
+
+    /// Wakes every thread registered since the last `wake_all` call.
+    pub fn wake_all(&self) {
+        if !self.has_sleepers.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        for t in self.sleepers.lock().unwrap().drain(..) {
+            t.unpark();
+        }
+    }
+
+    /// Removes every registration for `thread` from the wait list.
+    /// `Select` uses this to drop its registration from the queues that
+    /// didn't fire, so selecting over a long-lived, imbalanced mix of
+    /// busy and idle queues doesn't grow their wait lists without bound.
+    pub fn unregister(&self, thread: &Thread) {
+        let mut sleepers = self.sleepers.lock().unwrap();
+        sleepers.retain(|t| t.id() != thread.id());
+        if sleepers.is_empty() {
+            self.has_sleepers.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// The async analogue of `Waker`: a wait list of `std::task::Waker`s,
+/// used by the `Stream`/`Sink` adapters so a task can be polled again
+/// instead of the queue spinning it. Guarded by the same `has_wakers`
+/// flag trick as `Waker`, for the same reason.
+pub struct TaskWaker {
+    has_wakers: AtomicBool,
+    wakers: Mutex<Vec<TaskWakerHandle>>,
+}
+
+impl TaskWaker {
+    pub fn new() -> TaskWaker {
+        TaskWaker {
+            has_wakers: AtomicBool::new(false),
+            wakers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers a task waker to be woken by the next `wake_all`.
+    pub fn register(&self, waker: &TaskWakerHandle) {
+        self.wakers.lock().unwrap().push(waker.clone());
+        self.has_wakers.store(true, Ordering::SeqCst);
+    }
+
+    /// Wakes every task waker registered since the last `wake_all` call.
+    pub fn wake_all(&self) {
+        if !self.has_wakers.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        for w in self.wakers.lock().unwrap().drain(..) {
+            w.wake();
+        }
+    }
+}